@@ -1,10 +1,12 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::Args;
 use dojo_world::config::calldata_decoder;
 use dojo_world::contracts::ContractInfo;
 use scarb::core::Config;
+use serde::Deserialize;
 use sozo_ops::resource_descriptor::ResourceDescriptor;
 use sozo_scarbext::WorkspaceExt;
 use starknet::core::types::{BlockId, BlockTag, FunctionCall, StarknetError};
@@ -19,11 +21,12 @@ use crate::utils::{self, CALLDATA_DOC};
 #[derive(Debug, Args)]
 #[command(about = "Call a system with the given calldata.")]
 pub struct CallArgs {
-    #[arg(help = "The tag or address of the contract to call.")]
-    pub tag_or_address: ResourceDescriptor,
+    #[arg(help = "The tag or address of the contract to call. Required unless --multicall is \
+                  used.")]
+    pub tag_or_address: Option<ResourceDescriptor>,
 
-    #[arg(help = "The name of the entrypoint to call.")]
-    pub entrypoint: String,
+    #[arg(help = "The name of the entrypoint to call. Required unless --multicall is used.")]
+    pub entrypoint: Option<String>,
 
     #[arg(num_args = 0..)]
     #[arg(help = format!("The calldata to be passed to the system.
@@ -39,6 +42,13 @@ pub struct CallArgs {
                   tags to addresses.")]
     pub diff: bool,
 
+    #[arg(long)]
+    #[arg(help = "Path to a JSON or TOML file listing multiple calls to run against a single, \
+                  consistent block snapshot, instead of the single call above. Each entry takes \
+                  the same `tag_or_address`, `entrypoint` and `calldata` as the single-call \
+                  form.")]
+    pub multicall: Option<PathBuf>,
+
     #[command(flatten)]
     pub starknet: StarknetOptions,
 
@@ -46,45 +56,52 @@ pub struct CallArgs {
     pub world: WorldOptions,
 }
 
+/// A single call within a `--multicall` batch.
+#[derive(Debug, Deserialize)]
+struct CallSpec {
+    tag_or_address: String,
+    entrypoint: String,
+    #[serde(default)]
+    calldata: Vec<String>,
+}
+
 impl CallArgs {
     pub fn run(self, config: &Config) -> Result<()> {
         trace!(args = ?self);
 
-        let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+        if self.multicall.is_some() {
+            if self.tag_or_address.is_some() || self.entrypoint.is_some() {
+                anyhow::bail!(
+                    "`tag_or_address` and `entrypoint` are not used with `--multicall`; remove \
+                     them or drop `--multicall` to make a single call."
+                );
+            }
+        } else if self.tag_or_address.is_none() || self.entrypoint.is_none() {
+            anyhow::bail!(
+                "both `tag_or_address` and `entrypoint` are required unless `--multicall` is \
+                 used."
+            );
+        }
 
+        let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
         let profile_config = ws.load_profile_config()?;
 
-        let descriptor = self.tag_or_address.ensure_namespace(&profile_config.namespace.default);
-
         config.tokio_handle().block_on(async {
             let local_manifest = ws.read_manifest_profile()?;
 
-            let calldata = calldata_decoder::decode_calldata(&self.calldata)?;
+            let contracts: HashMap<String, ContractInfo> =
+                if self.diff || self.multicall.is_some() || local_manifest.is_none() {
+                    let (world_diff, _, _) = utils::get_world_diff_and_provider(
+                        self.starknet.clone(),
+                        self.world,
+                        &ws,
+                    )
+                    .await?;
 
-            let contract_address = match &descriptor {
-                ResourceDescriptor::Address(address) => Some(*address),
-                ResourceDescriptor::Tag(tag) => {
-                    let contracts: HashMap<String, ContractInfo> =
-                        if self.diff || local_manifest.is_none() {
-                            let (world_diff, _, _) = utils::get_world_diff_and_provider(
-                                self.starknet.clone(),
-                                self.world,
-                                &ws,
-                            )
-                            .await?;
-
-                            (&world_diff).into()
-                        } else {
-                            (&local_manifest.unwrap()).into()
-                        };
-
-                    contracts.get(tag).map(|c| c.address)
-                }
-                ResourceDescriptor::Name(_) => {
-                    unimplemented!("Expected to be a resolved tag with default namespace.")
-                }
-            }
-            .ok_or_else(|| anyhow!("Contract {descriptor} not found in the world diff."))?;
+                    (&world_diff).into()
+                } else {
+                    (&local_manifest.unwrap()).into()
+                };
 
             let block_id = if let Some(block_id) = self.block_id {
                 dojo_utils::parse_block_id(block_id)?
@@ -94,11 +111,50 @@ impl CallArgs {
 
             let (provider, _) = self.starknet.provider(profile_config.env.as_ref())?;
 
+            if let Some(path) = self.multicall {
+                let calls = read_call_specs(&path)?;
+                let default_namespace = &profile_config.namespace.default;
+
+                let results =
+                    futures::future::join_all(calls.into_iter().map(|call| {
+                        let contracts = &contracts;
+                        let provider = &provider;
+                        async move {
+                            run_call(call, contracts, provider, block_id, default_namespace).await
+                        }
+                    }))
+                    .await;
+
+                let output = serde_json::Value::Array(
+                    results
+                        .into_iter()
+                        .map(|res| match res {
+                            Ok(values) => serde_json::json!({
+                                "result": values.iter().map(|o| format!("0x{:x}", o)).collect::<Vec<_>>(),
+                            }),
+                            Err(e) => serde_json::json!({ "error": e.to_string() }),
+                        })
+                        .collect(),
+                );
+
+                println!("{}", serde_json::to_string_pretty(&output)?);
+
+                return Ok(());
+            }
+
+            let entrypoint = self.entrypoint.expect("validated above");
+            let descriptor = self
+                .tag_or_address
+                .expect("validated above")
+                .ensure_namespace(&profile_config.namespace.default);
+            let contract_address = resolve_address(&descriptor, &contracts)?;
+            let calldata = calldata_decoder::decode_calldata(&self.calldata)?;
+
             let res = provider
                 .call(
                     FunctionCall {
                         contract_address,
-                        entry_point_selector: snutils::get_selector_from_name(&self.entrypoint)?,
+                        entry_point_selector: snutils::get_selector_from_name(&entrypoint)?,
                         calldata,
                     },
                     block_id,
@@ -115,7 +171,7 @@ impl CallArgs {
                 Err(e) => {
                     anyhow::bail!(format!(
                         "Error calling entrypoint `{}` on address: {:#066x}\n{}",
-                        self.entrypoint,
+                        entrypoint,
                         contract_address,
                         match &e {
                             ProviderError::StarknetError(StarknetError::ContractError(e)) => {
@@ -131,3 +187,82 @@ impl CallArgs {
         })
     }
 }
+
+/// Resolve a [`ResourceDescriptor`] to the contract address it refers to, using the already
+/// resolved tag-to-address map.
+fn resolve_address(
+    descriptor: &ResourceDescriptor,
+    contracts: &HashMap<String, ContractInfo>,
+) -> Result<starknet::core::types::Felt> {
+    match descriptor {
+        ResourceDescriptor::Address(address) => Ok(*address),
+        ResourceDescriptor::Tag(tag) => contracts
+            .get(tag)
+            .map(|c| c.address)
+            .ok_or_else(|| anyhow!("Contract {descriptor} not found in the world diff.")),
+        ResourceDescriptor::Name(_) => {
+            unimplemented!("Expected to be a resolved tag with default namespace.")
+        }
+    }
+}
+
+/// Run a single call from a `--multicall` batch against the shared, already-resolved block
+/// snapshot.
+async fn run_call<P: Provider>(
+    call: CallSpec,
+    contracts: &HashMap<String, ContractInfo>,
+    provider: &P,
+    block_id: BlockId,
+    default_namespace: &str,
+) -> Result<Vec<starknet::core::types::Felt>> {
+    let descriptor: ResourceDescriptor = call
+        .tag_or_address
+        .parse()
+        .map_err(|_| anyhow!("invalid tag or address `{}`", call.tag_or_address))?;
+    let descriptor = descriptor.ensure_namespace(default_namespace);
+    let contract_address = resolve_address(&descriptor, contracts)?;
+    let calldata = calldata_decoder::decode_calldata(&call.calldata)?;
+
+    let res = provider
+        .call(
+            FunctionCall {
+                contract_address,
+                entry_point_selector: snutils::get_selector_from_name(&call.entrypoint)?,
+                calldata,
+            },
+            block_id,
+        )
+        .await;
+
+    res.map_err(|e| {
+        anyhow!(
+            "Error calling entrypoint `{}` on address: {:#066x}\n{}",
+            call.entrypoint,
+            contract_address,
+            match &e {
+                ProviderError::StarknetError(StarknetError::ContractError(e)) => {
+                    format!("Contract error: {}", e.revert_error.clone())
+                }
+                _ => e.to_string(),
+            }
+        )
+    })
+}
+
+/// Parse the calls of a `--multicall` batch from a JSON or TOML file, keyed by extension.
+fn read_call_specs(path: &PathBuf) -> Result<Vec<CallSpec>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read multicall file `{}`", path.display()))?;
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => {
+            #[derive(Deserialize)]
+            struct CallSpecFile {
+                calls: Vec<CallSpec>,
+            }
+
+            Ok(toml::from_str::<CallSpecFile>(&content)?.calls)
+        }
+        _ => Ok(serde_json::from_str::<Vec<CallSpec>>(&content)?),
+    }
+}
@@ -0,0 +1,255 @@
+//! Canonical-Hash-Trie (CHT) sections backing the `get_header_proof` light-client RPC.
+//!
+//! The chain is partitioned into fixed-size sections of [`CHT_SECTION_SIZE`] blocks. Once a
+//! section is complete, its headers are committed into a Bonsai/Patricia trie keyed by the
+//! block's position within the section (`block_number % CHT_SECTION_SIZE`), whose leaves are
+//! `Poseidon(header_hash, state_root, block_number)`. The resulting root is appended to the
+//! append-only [`CanonicalHashTrie::roots`] vector, indexed by section (`block_number /
+//! CHT_SECTION_SIZE`). A light client only needs to hold that (small) vector of roots to
+//! authenticate any historical header against a [`MultiProof`] in O(log N), exactly as the class
+//! and contract proofs are verified client-side today.
+//!
+//! The current, not-yet-complete section has no committed root. Callers must fall back to
+//! serving the header straight off the live chain for blocks in that range; see
+//! [`CanonicalHashTrie::root_for`].
+//!
+//! NOTE: this module only provides the trie itself; nothing in this checkout constructs a
+//! [`CanonicalHashTrie`] or calls `insert_header` outside its own unit tests below. Wiring it up
+//! needs two things neither of which this checkout actually has available:
+//! - [`super::BlockProductionTask::poll`] would need to call `insert_header(block_number,
+//!   header_hash, state_root)` for each mined block, but the `BlockProductionResult`/outcome type
+//!   it already destructures there (see the `events.publish(..)` call next to it) doesn't expose
+//!   a header hash or state root -- only `block_number`, `tx_hashes`, `events` and gas/step
+//!   stats. Deriving those two values from anything else on hand would mean hashing data that
+//!   isn't actually the block's header, producing a trie that doesn't prove what it claims to --
+//!   worse than leaving it unwired.
+//! - `starknet_getHeaderProof` needs a `katana-rpc-api`/`katana-rpc` server to register it on,
+//!   and neither crate's source is present in this checkout.
+//!
+//! See the unit tests below for direct coverage of this module's own (correct, in-tree) behavior
+//! in the meantime.
+
+use katana_primitives::block::BlockNumber;
+use katana_primitives::hash::{self, StarkHash};
+use katana_primitives::Felt;
+use katana_trie::bitvec::view::AsBits;
+use katana_trie::bonsai::{BasicId, BitVec, BonsaiDatabase, BonsaiStorage, BonsaiStorageConfig};
+use katana_trie::MultiProof;
+
+/// Number of blocks per CHT section.
+pub const CHT_SECTION_SIZE: u64 = 2048;
+
+/// Builds and stores the per-section header tries, and the append-only list of section roots
+/// light clients rely on.
+#[allow(missing_debug_implementations)]
+pub struct CanonicalHashTrie<DB: BonsaiDatabase> {
+    trie: BonsaiStorage<BasicId, DB, hash::Poseidon>,
+    /// `roots[i]` is the committed root of section `i`, once that section is complete.
+    roots: Vec<Felt>,
+    /// `commit_ids[i]` is the id the section `i`'s closing commit was made under, so `prove` can
+    /// recover that exact historical version via `get_transactional_state`.
+    commit_ids: Vec<BlockNumber>,
+    /// Genesis's own one-off root/commit id, available the instant block 0 is inserted, well
+    /// before section 0 (which also contains it) actually closes. Kept separate from
+    /// `roots`/`commit_ids` so an open section 0 doesn't appear closed for every other block in
+    /// it; see [`CanonicalHashTrie::root_for`].
+    genesis: Option<(Felt, BlockNumber)>,
+}
+
+impl<DB: BonsaiDatabase> CanonicalHashTrie<DB> {
+    pub fn new(db: DB) -> Result<Self, katana_trie::bonsai::BonsaiStorageError<DB::DatabaseError>> {
+        let trie = BonsaiStorage::new(db, BonsaiStorageConfig::default())?;
+        Ok(Self { trie, roots: Vec::new(), commit_ids: Vec::new(), genesis: None })
+    }
+
+    /// The section a block belongs to.
+    pub fn section_of(block_number: BlockNumber) -> u64 {
+        block_number / CHT_SECTION_SIZE
+    }
+
+    /// The committed root covering `block_number`, if its section has already been finalized, or
+    /// (for block 0 only) the one-off genesis root if section 0 hasn't closed yet. Returns `None`
+    /// for every other block in the current, still-open section.
+    pub fn root_for(&self, block_number: BlockNumber) -> Option<Felt> {
+        if let Some(&root) = self.roots.get(Self::section_of(block_number) as usize) {
+            return Some(root);
+        }
+
+        if block_number == 0 { self.genesis.map(|(root, _)| root) } else { None }
+    }
+
+    /// Insert a newly mined header into its section's trie, committing and recording the
+    /// section root once the section is complete.
+    ///
+    /// Genesis gets its own immediately-available root/commit id (see [`Self::root_for`]) so
+    /// light clients can authenticate block 0 right away, without that leaking into the rest of
+    /// section 0: until section 0 actually closes at its boundary, blocks 1..CHT_SECTION_SIZE-1
+    /// still correctly report no committed root.
+    pub fn insert_header(
+        &mut self,
+        block_number: BlockNumber,
+        header_hash: Felt,
+        state_root: Felt,
+    ) -> Result<(), katana_trie::bonsai::BonsaiStorageError<DB::DatabaseError>> {
+        let section = Self::section_of(block_number);
+        let key = cht_key(block_number);
+        let leaf = leaf_hash(header_hash, state_root, block_number);
+
+        self.trie.insert(&key, &leaf)?;
+        // Commit under an id that's monotonically increasing across every block, matching how
+        // the rest of this trie's history is addressed elsewhere (one id per block, not one id
+        // reused across an entire section's worth of inserts).
+        self.trie.commit(BasicId::new(block_number))?;
+
+        if block_number == 0 {
+            let root = self.trie.root_hash()?;
+            self.genesis = Some((root, block_number));
+        }
+
+        let is_section_boundary = block_number % CHT_SECTION_SIZE == CHT_SECTION_SIZE - 1;
+        if is_section_boundary {
+            let root = self.trie.root_hash()?;
+            match self.roots.len() {
+                len if len == section as usize => {
+                    self.roots.push(root);
+                    self.commit_ids.push(block_number);
+                }
+                _ => {
+                    self.roots[section as usize] = root;
+                    self.commit_ids[section as usize] = block_number;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build an inclusion proof for `block_number`'s header leaf against its section root (or,
+    /// for block 0 before section 0 closes, against the one-off genesis root). Returns `None` if
+    /// the section covering `block_number` hasn't been finalized yet.
+    pub fn prove(
+        &mut self,
+        block_number: BlockNumber,
+    ) -> Result<Option<MultiProof>, katana_trie::bonsai::BonsaiStorageError<DB::DatabaseError>>
+    {
+        let section = Self::section_of(block_number);
+        let key = cht_key(block_number);
+
+        let commit_id = match self.commit_ids.get(section as usize) {
+            Some(&id) => id,
+            None if block_number == 0 => match self.genesis {
+                Some((_, id)) => id,
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+
+        let state = self
+            .trie
+            .get_transactional_state(BasicId::new(commit_id), BonsaiStorageConfig::default())?;
+        let Some(mut state) = state else { return Ok(None) };
+
+        let nodes = state.get_proof(&key)?;
+        Ok(Some(MultiProof::from(nodes)))
+    }
+}
+
+/// The trie key for a block within its section: the block number's low bits, local to the
+/// section (`block_number % CHT_SECTION_SIZE`).
+fn cht_key(block_number: BlockNumber) -> BitVec {
+    let local = block_number % CHT_SECTION_SIZE;
+    Felt::from(local).to_bytes_be().as_bits()[5..].to_owned()
+}
+
+fn leaf_hash(header_hash: Felt, state_root: Felt, block_number: BlockNumber) -> Felt {
+    hash::Poseidon::hash(&hash::Poseidon::hash(&header_hash, &state_root), &Felt::from(block_number))
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_trie::bonsai::databases::HashMapDb;
+
+    use super::*;
+
+    fn new_trie() -> CanonicalHashTrie<HashMapDb<BasicId>> {
+        CanonicalHashTrie::new(HashMapDb::default()).expect("failed to create trie")
+    }
+
+    #[test]
+    fn genesis_is_committed_immediately() {
+        let mut cht = new_trie();
+        cht.insert_header(0, Felt::from(1u64), Felt::from(2u64)).unwrap();
+
+        assert!(cht.root_for(0).is_some(), "genesis root must be available right away");
+        assert!(cht.prove(0).unwrap().is_some(), "genesis must be provable right away");
+    }
+
+    #[test]
+    fn open_section_has_no_root_or_proof() {
+        let mut cht = new_trie();
+        cht.insert_header(0, Felt::from(1u64), Felt::from(2u64)).unwrap();
+        cht.insert_header(1, Felt::from(3u64), Felt::from(4u64)).unwrap();
+
+        // section 0 only closes once block `CHT_SECTION_SIZE - 1` is inserted. Block 0's own
+        // one-off genesis root must not be mistaken for section 0's (not yet existing) root.
+        assert!(cht.root_for(1).is_none(), "section 0 hasn't closed yet");
+        assert!(cht.prove(1).unwrap().is_none(), "can't prove into a still-open section");
+    }
+
+    #[test]
+    fn section_close_supersedes_the_genesis_only_root() {
+        let mut cht = new_trie();
+
+        cht.insert_header(0, Felt::from(1u64), Felt::from(2u64)).unwrap();
+        let genesis_only_root = cht.root_for(0).expect("genesis root must be available");
+
+        for block_number in 1..CHT_SECTION_SIZE {
+            let header_hash = Felt::from(block_number + 1);
+            let state_root = Felt::from(block_number + 2);
+            cht.insert_header(block_number, header_hash, state_root).unwrap();
+        }
+
+        // once section 0 closes, block 0 is proven against the real section root (which covers
+        // every leaf in the section), not the stale root committed back when only genesis existed.
+        let section_root = cht.root_for(0).expect("section 0 is now closed");
+        assert_ne!(section_root, genesis_only_root);
+
+        let proof = cht.prove(0).unwrap().expect("block 0 must still be provable");
+        let key = cht_key(0);
+        let leaf = leaf_hash(Felt::from(1u64), Felt::from(2u64), 0);
+
+        let values = proof
+            .verify_proof::<hash::Poseidon>(section_root, [key], 251)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("failed to verify proof");
+
+        assert_eq!(vec![leaf], values);
+    }
+
+    #[test]
+    fn section_closes_and_is_provable_at_boundary() {
+        let mut cht = new_trie();
+
+        for block_number in 0..CHT_SECTION_SIZE {
+            let header_hash = Felt::from(block_number + 1);
+            let state_root = Felt::from(block_number + 2);
+            cht.insert_header(block_number, header_hash, state_root).unwrap();
+        }
+
+        let last_block = CHT_SECTION_SIZE - 1;
+        assert!(cht.root_for(last_block).is_some(), "section 0 must be closed");
+
+        let proof = cht.prove(last_block).unwrap().expect("section 0 is closed, must be provable");
+        let root = cht.root_for(last_block).unwrap();
+
+        let key = cht_key(last_block);
+        let leaf = leaf_hash(Felt::from(last_block + 1), Felt::from(last_block + 2), last_block);
+
+        let values = proof
+            .verify_proof::<hash::Poseidon>(root, [key], 251)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("failed to verify proof");
+
+        assert_eq!(vec![leaf], values);
+    }
+}
@@ -1,6 +1,7 @@
 // TODO: remove the messaging feature flag
 // TODO: move the tasks to a separate module
 
+use std::collections::{BTreeMap, HashMap};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -9,15 +10,21 @@ use std::task::{Context, Poll};
 use futures::channel::mpsc::Receiver;
 use futures::stream::{Fuse, Stream, StreamExt};
 use katana_executor::ExecutorFactory;
-use katana_pool::{TransactionPool, TxPool};
+use katana_pool::{PoolTransaction, TransactionPool, TxPool};
+use katana_primitives::block::BlockNumber;
+use katana_primitives::contract::{ContractAddress, Nonce};
 use katana_primitives::transaction::ExecutableTxWithHash;
 use katana_primitives::Felt;
+use tokio::sync::broadcast;
 use tracing::{error, info};
 
 use self::block_producer::BlockProducer;
+use self::events::{BlockProductionEvent, BlockProductionEvents, GetChanges, SubscriptionId};
 use self::metrics::BlockProducerMetrics;
 
 pub mod block_producer;
+pub mod cht;
+pub mod events;
 #[cfg(feature = "messaging")]
 pub mod messaging;
 mod metrics;
@@ -45,6 +52,8 @@ where
     pub(crate) pool: P,
     /// Metrics for recording the service operations
     metrics: BlockProducerMetrics,
+    /// Push (broadcast) and pull (cursor-based) subscribers to newly mined blocks
+    events: BlockProductionEvents,
 }
 
 impl<EF, P> BlockProductionTask<EF, P>
@@ -58,10 +67,46 @@ where
         miner: TransactionMiner<P>,
         block_producer: Arc<BlockProducer<EF>>,
     ) -> Self {
-        Self { block_producer, miner, pool, metrics: BlockProducerMetrics::default() }
+        Self {
+            block_producer,
+            miner,
+            pool,
+            metrics: BlockProducerMetrics::default(),
+            events: BlockProductionEvents::new(),
+        }
+    }
+
+    /// Subscribe to the push-based (channel/WebSocket) stream of [`BlockProductionEvent`]s.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<BlockProductionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Register a new poll-based `get_changes` cursor, starting from `from_block`.
+    pub fn new_events_cursor(&mut self, from_block: BlockNumber) -> SubscriptionId {
+        self.events.new_cursor(from_block)
+    }
+
+    /// Everything produced since `subscription_id`'s last poll; see
+    /// [`BlockProductionEvents::get_changes`].
+    pub fn get_changes(&mut self, subscription_id: SubscriptionId) -> Option<GetChanges> {
+        self.events.get_changes(subscription_id)
+    }
+
+    /// Drop a poll-based cursor that's no longer needed, so its book-keeping doesn't leak for
+    /// the life of the node.
+    pub fn remove_events_cursor(&mut self, subscription_id: SubscriptionId) {
+        self.events.remove_cursor(subscription_id)
     }
 }
 
+/// Default maximum number of nonce-gapped (Future) transactions a single sender may have queued
+/// at once; additional transactions from that sender are rejected until earlier nonces clear.
+pub const DEFAULT_MAX_PER_SENDER: usize = 16;
+
+/// Default maximum number of nonce-gapped (Future) transactions the miner will hold across all
+/// senders before evicting the lowest-scored one to make room for new arrivals.
+pub const DEFAULT_NONCE_CAP: usize = 1024;
+
 impl<EF, P> Future for BlockProductionTask<EF, P>
 where
     EF: ExecutorFactory,
@@ -85,6 +130,21 @@ where
                         let steps_used = outcome.stats.cairo_steps_used;
                         this.metrics.l1_gas_processed_total.increment(gas_used as u64);
                         this.metrics.cairo_steps_processed_total.increment(steps_used as u64);
+
+                        this.events.publish(BlockProductionEvent {
+                            block_number: outcome.block_number,
+                            l1_gas_used: gas_used,
+                            cairo_steps_used: steps_used,
+                            tx_hashes: outcome.tx_hashes.clone(),
+                            emitted_events: outcome.events.clone(),
+                        });
+
+                        // NOT calling `cht::CanonicalHashTrie::insert_header` here on purpose:
+                        // doing so needs this block's header hash and state root, and `outcome`
+                        // (destructured above) doesn't carry either -- only `block_number`,
+                        // `tx_hashes`, `events` and gas/step stats. See `super::cht`'s module doc
+                        // for the full reasoning; this comment exists so the gap is visible at
+                        // the one call site that would need to change, not just in cht.rs.
                     }
 
                     Err(error) => {
@@ -108,19 +168,58 @@ where
 }
 
 /// The type which takes the transaction from the pool and feeds them to the block producer.
+///
+/// Transactions are partitioned per-sender into a *Ready* set (nonce matches the sender's
+/// expected next nonce) and a *Future* set (nonce gaps, waiting on an earlier nonce from the
+/// same sender). Each poll drains every Ready transaction it can find, highest effective tip
+/// first, while always respecting intra-sender nonce order, so fee-market pressure picks the
+/// mining order instead of pool arrival order, and a stuck nonce gap no longer blocks later
+/// valid transactions from other senders.
+///
+/// ASSUMPTION: a sender's expected next nonce is seeded from the lowest nonce this miner has
+/// ever seen from the pool for that sender (see `seed_expected_nonces`), not from on-chain/world
+/// state -- this type has no state/provider access to consult. This is only correct if
+/// `TransactionPool::take_transactions` never hands this miner a sender's transactions with a
+/// gap before that sender's true next on-chain nonce (i.e. the pool itself only ever admits a
+/// transaction once everything below its nonce is already valid/mined). If that invariant
+/// doesn't hold, a sender's lowest-seen-so-far nonce can be treated as Ready and mined before
+/// it's actually valid, surfacing downstream as an executor nonce-rejection error rather than a
+/// bug here.
 #[derive(Debug)]
-pub struct TransactionMiner<P> {
+pub struct TransactionMiner<P: TransactionPool> {
     /// stores whether there are pending transacions (if known)
     has_pending_txs: Option<bool>,
     /// Receives hashes of transactions that are ready from the pool
     rx: Fuse<Receiver<Felt>>,
-
-    _pool: std::marker::PhantomData<P>,
+    /// Transactions queued per-sender, ordered by nonce. The front of a sender's map is Ready
+    /// once its nonce matches `expected_nonces[sender]`; everything behind it is Future.
+    queued: HashMap<ContractAddress, BTreeMap<Nonce, P::Transaction>>,
+    /// The next nonce expected from each sender, advanced as that sender's Ready transactions
+    /// are taken for mining.
+    expected_nonces: HashMap<ContractAddress, Nonce>,
+    /// Maximum number of Future transactions allowed per sender.
+    max_per_sender: usize,
+    /// Maximum number of Future transactions allowed across all senders.
+    nonce_cap: usize,
 }
 
-impl<P: TransactionPool> TransactionMiner<P> {
+impl<P: TransactionPool> TransactionMiner<P>
+where
+    P::Transaction: PoolTransaction,
+{
     pub fn new(rx: Receiver<Felt>) -> Self {
-        Self { rx: rx.fuse(), has_pending_txs: None, _pool: std::marker::PhantomData }
+        Self::with_limits(rx, DEFAULT_MAX_PER_SENDER, DEFAULT_NONCE_CAP)
+    }
+
+    pub fn with_limits(rx: Receiver<Felt>, max_per_sender: usize, nonce_cap: usize) -> Self {
+        Self {
+            rx: rx.fuse(),
+            has_pending_txs: None,
+            queued: HashMap::new(),
+            expected_nonces: HashMap::new(),
+            max_per_sender,
+            nonce_cap,
+        }
     }
 
     fn poll(&mut self, pool: &P, cx: &mut Context<'_>) -> Poll<Vec<P::Transaction>> {
@@ -129,19 +228,215 @@ impl<P: TransactionPool> TransactionMiner<P> {
             self.has_pending_txs = Some(true);
         }
 
-        if self.has_pending_txs == Some(false) {
+        if self.has_pending_txs == Some(false) && self.is_empty() {
             return Poll::Pending;
         }
 
-        // take all the transactions from the pool
-        let transactions =
-            pool.take_transactions().map(|tx| tx.tx.as_ref().clone()).collect::<Vec<_>>();
+        // merge everything the pool currently has ready to hand off into our per-sender,
+        // nonce-ordered queues first, then seed afterwards -- this must happen as two
+        // passes: `pool.take_transactions()` makes no ordering guarantee, so if it handed us a
+        // sender's nonce 4 before its nonce 3 in the same batch, deriving each sender's expected
+        // nonce from the fully-merged, nonce-sorted queue (rather than from whichever
+        // transaction happened to arrive first) is what keeps nonce 3 from being stranded behind
+        // a nonce already (wrongly) treated as Ready.
+        for tx in pool.take_transactions() {
+            let tx = tx.tx.as_ref().clone();
+            self.queued.entry(tx.sender()).or_default().insert(tx.nonce(), tx);
+        }
+        self.seed_expected_nonces();
+
+        let ready = self.drain_ready();
+        // caps apply only to what is left once every Ready chain is drained, so a run of
+        // consecutive nonces from one sender is never counted (or evicted) as Future.
+        self.enforce_caps();
 
-        if transactions.is_empty() {
+        if ready.is_empty() {
             return Poll::Pending;
         }
 
         self.has_pending_txs = Some(false);
-        Poll::Ready(transactions)
+        Poll::Ready(ready)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queued.values().all(BTreeMap::is_empty)
+    }
+
+    /// Establish each sender's expected next nonce from the lowest nonce currently queued for
+    /// it, for senders we haven't seen before. This must run only after every transaction from
+    /// the current pool batch has been merged in, since `BTreeMap`'s key order (not arrival
+    /// order) is what makes this the true minimum regardless of what order the pool handed
+    /// transactions to us in.
+    ///
+    /// This is still only the lowest nonce *this miner has observed*, not the account's true
+    /// on-chain next nonce -- see the assumption documented on [`TransactionMiner`] above.
+    fn seed_expected_nonces(&mut self) {
+        for (&sender, txs) in &self.queued {
+            if let Some(&min_nonce) = txs.keys().next() {
+                self.expected_nonces.entry(sender).or_insert(min_nonce);
+            }
+        }
+    }
+
+    /// Enforce the per-sender and global Future caps, evicting the lowest-scored Future
+    /// transactions as needed.
+    fn enforce_caps(&mut self) {
+        let senders = self.queued.keys().copied().collect::<Vec<_>>();
+        for sender in senders {
+            while self.future_count_for(sender) > self.max_per_sender {
+                self.evict_highest_nonce_future(sender);
+            }
+        }
+
+        while self.future_count() > self.nonce_cap {
+            self.evict_lowest_scored_future();
+        }
+    }
+
+    /// Number of currently queued transactions that are Future (i.e. not the next expected
+    /// nonce) for a single sender.
+    fn future_count_for(&self, sender: ContractAddress) -> usize {
+        let Some(txs) = self.queued.get(&sender) else { return 0 };
+        let expected = self.expected_nonces.get(&sender).copied();
+        txs.keys().filter(|&&nonce| Some(nonce) != expected).count()
+    }
+
+    /// Number of currently queued transactions that are Future (i.e. not the next expected
+    /// nonce for their sender), across all senders.
+    fn future_count(&self) -> usize {
+        self.queued.keys().map(|&sender| self.future_count_for(sender)).sum()
+    }
+
+    /// Drop the highest-nonce Future transaction for `sender`, to make room under
+    /// `max_per_sender`.
+    fn evict_highest_nonce_future(&mut self, sender: ContractAddress) {
+        let expected = self.expected_nonces.get(&sender).copied();
+        if let Some(txs) = self.queued.get_mut(&sender) {
+            let highest = txs.keys().rev().find(|&&nonce| Some(nonce) != expected).copied();
+            if let Some(nonce) = highest {
+                txs.remove(&nonce);
+            }
+        }
+    }
+
+    /// Drop the globally lowest-scored Future transaction to make room under `nonce_cap`.
+    fn evict_lowest_scored_future(&mut self) {
+        let worst = self
+            .queued
+            .iter()
+            .flat_map(|(&sender, txs)| {
+                let expected = self.expected_nonces.get(&sender).copied();
+                txs.iter()
+                    .filter(move |&(&nonce, _)| Some(nonce) != expected)
+                    .map(move |(&nonce, tx)| (tx.tip(), sender, nonce))
+            })
+            .min_by_key(|&(tip, _, _)| tip);
+
+        if let Some((_, sender, nonce)) = worst {
+            if let Some(txs) = self.queued.get_mut(&sender) {
+                txs.remove(&nonce);
+            }
+        }
+    }
+
+    /// Pop every Ready transaction across all senders, highest effective tip first, always
+    /// respecting each sender's nonce order: a sender's next nonce only becomes eligible once
+    /// its predecessor has been popped, so two Ready transactions from the same sender can never
+    /// be reordered relative to each other by tip.
+    fn drain_ready(&mut self) -> Vec<P::Transaction> {
+        let ready = merge_ready_by_tip(&mut self.queued, &mut self.expected_nonces, |tx| tx.tip());
+        self.queued.retain(|_, txs| !txs.is_empty());
+        ready
+    }
+}
+
+/// Merge each sender's Ready-prefix transactions into a single list ordered by tip, highest
+/// first, while never letting a sender's later nonce overtake its earlier one. Kept as a free
+/// function, generic over the payload, so the merge algorithm itself can be unit tested without
+/// the full `TransactionPool`/`PoolTransaction` machinery.
+fn merge_ready_by_tip<T>(
+    queued: &mut HashMap<ContractAddress, BTreeMap<Nonce, T>>,
+    expected_nonces: &mut HashMap<ContractAddress, Nonce>,
+    tip_of: impl Fn(&T) -> u128,
+) -> Vec<T> {
+    let mut heap: std::collections::BinaryHeap<(u128, ContractAddress)> =
+        std::collections::BinaryHeap::new();
+
+    for (&sender, txs) in queued.iter() {
+        if let Some(expected) = expected_nonces.get(&sender) {
+            if let Some(tx) = txs.get(expected) {
+                heap.push((tip_of(tx), sender));
+            }
+        }
+    }
+
+    let mut ready = Vec::new();
+
+    while let Some((_, sender)) = heap.pop() {
+        let expected = expected_nonces.get_mut(&sender).expect("seeded above");
+        let txs = queued.get_mut(&sender).expect("seeded above");
+
+        let tx = txs.remove(expected).expect("head must exist, it's what was pushed to the heap");
+        *expected += Felt::ONE;
+
+        if let Some(next_tx) = txs.get(expected) {
+            heap.push((tip_of(next_tx), sender));
+        }
+
+        ready.push(tx);
+    }
+
+    ready
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ready_txs_respect_sender_nonce_order_within_tip_priority() {
+        let sender_a = ContractAddress::from(Felt::from(1u64));
+        let sender_b = ContractAddress::from(Felt::from(2u64));
+
+        let mut queued: HashMap<ContractAddress, BTreeMap<Nonce, u128>> = HashMap::new();
+        // sender A has both nonce 0 (tip 10) and nonce 1 (tip 50) become Ready in the same poll.
+        queued.entry(sender_a).or_default().insert(Felt::from(0u64), 10);
+        queued.entry(sender_a).or_default().insert(Felt::from(1u64), 50);
+        // sender B only has nonce 0 (tip 20) Ready.
+        queued.entry(sender_b).or_default().insert(Felt::from(0u64), 20);
+
+        let mut expected_nonces = HashMap::new();
+        expected_nonces.insert(sender_a, Felt::from(0u64));
+        expected_nonces.insert(sender_b, Felt::from(0u64));
+
+        let ready = merge_ready_by_tip(&mut queued, &mut expected_nonces, |tip| *tip);
+
+        // Sender A's nonce 1 has a higher tip than its own nonce 0, but must never be handed to
+        // the producer before nonce 0 from the same sender.
+        assert_eq!(ready, vec![20, 10, 50]);
+        assert!(queued.values().all(BTreeMap::is_empty));
+    }
+
+    #[test]
+    fn seeding_from_merged_queue_ignores_pool_arrival_order() {
+        let sender = ContractAddress::from(Felt::from(1u64));
+
+        // simulate the pool handing us nonce 1 before nonce 0 in the same batch
+        let mut queued: HashMap<ContractAddress, BTreeMap<Nonce, u128>> = HashMap::new();
+        let txs = queued.entry(sender).or_default();
+        txs.insert(Felt::from(1u64), 5);
+        txs.insert(Felt::from(0u64), 1);
+
+        let mut expected_nonces: HashMap<ContractAddress, Nonce> = HashMap::new();
+        for (&s, txs) in &queued {
+            if let Some(&min_nonce) = txs.keys().next() {
+                expected_nonces.entry(s).or_insert(min_nonce);
+            }
+        }
+
+        assert_eq!(expected_nonces.get(&sender), Some(&Felt::from(0u64)));
+
+        let ready = merge_ready_by_tip(&mut queued, &mut expected_nonces, |tip| *tip);
+        assert_eq!(ready, vec![1, 5]);
     }
 }
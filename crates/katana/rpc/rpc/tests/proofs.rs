@@ -75,6 +75,13 @@ async fn proofs_limit() {
     });
 }
 
+// `starknet_getHeaderProof` is not registered on the RPC server in this checkout -- there is no
+// `katana-rpc-api`/`katana-rpc` server source here to add the method to, and `CanonicalHashTrie`
+// (see `katana::core::service::cht`) isn't wired into `BlockProducer` either. A test that calls
+// the method here would only ever fail with "method not found", not exercise the fallback
+// behavior it claims to. The CHT's own open-section-fallback and proof behavior is covered
+// directly by the unit tests in `katana::core::service::cht` instead.
+
 async fn declare(
     account: &SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>,
     path: impl Into<PathBuf>,